@@ -0,0 +1,153 @@
+//! Parser for the kernel command line handed over via [`super::RawBootInfo`].
+
+/// Parses a kernel command line into the CPU frequency override, `KEY=VALUE`
+/// environment entries, and the application argument tail.
+///
+/// The grammar is a whitespace-separated token list:
+/// - a `freq=<mhz>` token sets the CPU frequency override,
+/// - any other `KEY=VALUE` token before a bare `--` token is an environment entry,
+/// - everything after a bare `--` token is the application argument tail.
+///
+/// An application argument, or the value of a `KEY=VALUE` token, may be
+/// wrapped in double quotes to include whitespace; the quotes are stripped
+/// from the returned value. A `KEY=VALUE` token wrapped in quotes as a whole
+/// is not unquoted.
+#[derive(Debug, Clone, Copy)]
+pub struct CmdlineParser<'a> {
+    cmdline: &'a str,
+}
+
+impl<'a> CmdlineParser<'a> {
+    /// Creates a parser for the given command line.
+    pub fn new(cmdline: &'a str) -> Self {
+        Self { cmdline }
+    }
+
+    /// CPU frequency override in MHz, as set by a `freq=<mhz>` token.
+    pub fn freq(&self) -> Option<u32> {
+        tokens(self.cmdline)
+            .take_while(|token| *token != "--")
+            .find_map(|token| token.strip_prefix("freq="))
+            .and_then(|value| unquote(value).parse().ok())
+    }
+
+    /// `KEY=VALUE` environment entries, in order, excluding `freq=`.
+    pub fn env_vars(&self) -> impl Iterator<Item = (&'a str, &'a str)> {
+        tokens(self.cmdline)
+            .take_while(|token| *token != "--")
+            .filter(|token| !token.starts_with("freq="))
+            .filter_map(|token| token.split_once('='))
+            .map(|(key, value)| (key, unquote(value)))
+    }
+
+    /// Application arguments, i.e. the tokens after a bare `--` token.
+    pub fn args(&self) -> impl Iterator<Item = &'a str> {
+        let mut rest = tokens(self.cmdline).skip_while(|token| *token != "--");
+        rest.next();
+        rest.map(unquote)
+    }
+}
+
+/// Splits a command line into whitespace-separated tokens, treating a pair
+/// of double quotes as delimiting whitespace-containing text rather than a
+/// token boundary.
+fn tokens(cmdline: &str) -> impl Iterator<Item = &str> {
+    let mut rest = cmdline.trim_start();
+    core::iter::from_fn(move || {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return None;
+        }
+
+        let mut in_quotes = false;
+        let mut end = rest.len();
+        for (i, c) in rest.char_indices() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                c if c.is_whitespace() && !in_quotes => {
+                    end = i;
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        let (token, remainder) = rest.split_at(end);
+        rest = remainder;
+        Some(token)
+    })
+}
+
+/// Strips a single pair of surrounding double quotes, if present.
+fn unquote(s: &str) -> &str {
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_freq() {
+        let parser = CmdlineParser::new("freq=2400 -- /bin/app");
+        assert_eq!(parser.freq(), Some(2400));
+    }
+
+    #[test]
+    fn missing_freq_is_none() {
+        let parser = CmdlineParser::new("KEY=VALUE -- /bin/app");
+        assert_eq!(parser.freq(), None);
+    }
+
+    #[test]
+    fn freq_after_separator_is_ignored() {
+        let parser = CmdlineParser::new("-- /bin/app freq=999");
+        assert_eq!(parser.freq(), None);
+    }
+
+    #[test]
+    fn quoted_freq_is_parsed() {
+        let parser = CmdlineParser::new(r#"freq="2400" -- /bin/app"#);
+        assert_eq!(parser.freq(), Some(2400));
+    }
+
+    #[test]
+    fn collects_env_vars_excluding_freq() {
+        let parser = CmdlineParser::new("freq=2400 FOO=bar BAZ=qux -- /bin/app");
+        let mut env_vars = parser.env_vars();
+        assert_eq!(env_vars.next(), Some(("FOO", "bar")));
+        assert_eq!(env_vars.next(), Some(("BAZ", "qux")));
+        assert_eq!(env_vars.next(), None);
+    }
+
+    #[test]
+    fn collects_args_after_separator() {
+        let parser = CmdlineParser::new("freq=2400 -- /bin/app --flag value");
+        let mut args = parser.args();
+        assert_eq!(args.next(), Some("/bin/app"));
+        assert_eq!(args.next(), Some("--flag"));
+        assert_eq!(args.next(), Some("value"));
+        assert_eq!(args.next(), None);
+    }
+
+    #[test]
+    fn missing_separator_yields_no_args() {
+        let parser = CmdlineParser::new("freq=2400 FOO=bar");
+        assert_eq!(parser.args().next(), None);
+    }
+
+    #[test]
+    fn quoted_values_keep_internal_whitespace() {
+        let parser = CmdlineParser::new(r#"FOO="hello world" -- "/bin/my app""#);
+
+        let mut env_vars = parser.env_vars();
+        assert_eq!(env_vars.next(), Some(("FOO", "hello world")));
+        assert_eq!(env_vars.next(), None);
+
+        let mut args = parser.args();
+        assert_eq!(args.next(), Some("/bin/my app"));
+        assert_eq!(args.next(), None);
+    }
+}
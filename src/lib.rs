@@ -1,6 +1,6 @@
 //! # RustyHermit's entry API.
 
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 #![cfg_attr(feature = "kernel", feature(const_ptr_offset_from))]
 #![cfg_attr(feature = "kernel", feature(const_refs_to_cell))]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
@@ -15,6 +15,11 @@ mod kernel;
 #[cfg(feature = "kernel")]
 pub use kernel::_Note;
 
+mod cmdline;
+pub use cmdline::CmdlineParser;
+
+use core::num::{NonZeroU32, NonZeroU64};
+use core::ops::Range;
 use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 
 pub type Entry = unsafe extern "C" fn(raw_boot_info: &'static RawBootInfo) -> !;
@@ -27,7 +32,13 @@ mod consts {
     /// The `desc` field will be 1 word, which specifies the hermit entry version.
     pub const NT_HERMIT_ENTRY_VERSION: u32 = 0x5a00;
 
-    pub const HERMIT_ENTRY_VERSION: u8 = 1;
+    /// Version 2 adds `device_tree`, riscv64 support, `load_offset`, `rsdp`
+    /// and `boot_loader_name` to [`super::RawBootInfo`]; a loader/kernel
+    /// pair must agree on this note before trusting those fields.
+    pub const HERMIT_ENTRY_VERSION: u8 = 2;
+
+    /// Expected value of [`super::RawBootInfo`]'s `magic_number` field.
+    pub const RAW_BOOT_INFO_MAGIC_NUMBER: u32 = 0xC0DE_CAFE;
 }
 
 #[cfg(feature = "loader")]
@@ -36,53 +47,171 @@ pub use consts::NT_HERMIT_ENTRY_VERSION;
 #[cfg(feature = "loader")]
 pub use consts::HERMIT_ENTRY_VERSION;
 
+pub use consts::RAW_BOOT_INFO_MAGIC_NUMBER;
+
 #[cfg(target_arch = "x86_64")]
 type SerialPortBase = u16;
 #[cfg(target_arch = "aarch64")]
 type SerialPortBase = u32;
+#[cfg(target_arch = "riscv64")]
+type SerialPortBase = u32;
 
+/// Platform-independent information about the machine the kernel was booted on.
 #[derive(Debug)]
-pub struct BootInfo {
-    /// Lowest physical memory address.
-    #[cfg(target_arch = "aarch64")]
-    pub ram_start: u64,
+pub struct HardwareInfo {
+    /// Range of usable physical memory.
+    pub phys_addr_range: Range<u64>,
 
-    /// Highest physical memory address.
-    pub limit: u64,
+    /// Serial port base address, if the boot loader provided one.
+    pub serial_port_base: Option<SerialPortBase>,
 
+    /// Physical address of a flattened device tree (FDT), if the boot loader provided one.
+    pub device_tree: Option<NonZeroU64>,
+
+    /// Hart ID of the boot processor, as passed in `a0` at entry.
+    #[cfg(target_arch = "riscv64")]
+    pub boot_hart_id: u64,
+}
+
+#[derive(Debug)]
+pub struct BootInfo {
     /// Start address of the loaded kernel image.
     pub base: u64,
 
     /// Size of the loaded kernel image in bytes.
     pub image_size: u64,
 
+    /// Difference between `base` and the kernel's link-time base address.
+    ///
+    /// The kernel adds this to the addend of every `R_*_RELATIVE` relocation
+    /// in its `.rela.dyn` before enabling paging. Zero for a non-relocatable
+    /// image, i.e. one that was loaded at its link-time address.
+    pub load_offset: u64,
+
     /// Kernel image TLS information.
     pub tls_info: TlsInfo,
 
-    /// Serial port base address.
-    pub uartport: SerialPortBase,
-
-    /// Discriminant determines if running on uhyve.
-    pub uhyve: u8,
+    /// Hardware properties that are independent of the boot platform.
+    pub hardware_info: HardwareInfo,
 
-    /// UHYVE ONLY: Boot time as Unix timestamp in microseconds.
-    pub boot_gtod: u64,
+    /// Boot platform the kernel was started from, together with its
+    /// platform-specific boot information.
+    pub platform_info: PlatformInfo,
+}
 
-    /// UHYVE ONLY: CPU frequency in MHz.
-    pub cpu_freq: u16,
+/// Boot platform the kernel was started from.
+///
+/// Each variant only carries the fields that are meaningful for that
+/// platform, so a kernel can no longer accidentally read, say, the uhyve
+/// CPU frequency while running under multiboot.
+#[derive(Debug)]
+pub enum PlatformInfo {
+    Multiboot {
+        /// Command line, as a (pointer, length) pair into the bootloader's memory.
+        command_line: Option<(u64, u64)>,
+
+        /// Address of the multiboot information structure.
+        #[cfg(target_arch = "x86_64")]
+        multiboot_info_addr: u64,
+    },
+    Uhyve {
+        /// Total number of CPUs made available by uhyve.
+        num_cpus: u32,
+
+        /// CPU frequency in MHz, if known.
+        cpu_freq: Option<NonZeroU32>,
+
+        /// Boot time as a Unix timestamp in microseconds.
+        boot_time: u64,
+    },
+    /// Booted by a generic loader, e.g. a UEFI application, instead of a
+    /// dedicated multiboot or uhyve loader.
+    LinuxBoot {
+        /// Command line, as a (pointer, length) pair into the bootloader's memory.
+        command_line: Option<(u64, u64)>,
+
+        /// Physical address of the ACPI RSDP, as obtained from firmware.
+        rsdp: Option<NonZeroU64>,
+
+        /// Boot loader name, as a (pointer, length) pair into the bootloader's memory.
+        boot_loader_name: Option<(u64, u64)>,
+    },
+}
 
-    /// UHYVE ONLY: Total number of CPUs available.
-    pub possible_cpus: u32,
+/// Error returned by [`TryFrom<&RawBootInfo>`] when the raw structure could
+/// not be interpreted as a [`BootInfo`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BootInfoError {
+    /// The `magic_number` field did not match [`RAW_BOOT_INFO_MAGIC_NUMBER`].
+    InvalidMagicNumber,
 
-    /// MULTIBOOT ONLY: Command line pointer.
-    pub cmdline: u64,
+    /// The `uhyve` discriminant did not match any known platform.
+    UnknownPlatform(u8),
+}
 
-    /// MULTIBOOT ONLY: Command line length.
-    pub cmdsize: u64,
+/// Values of [`RawBootInfo`]'s `uhyve` discriminant.
+mod platform_discriminant {
+    pub const MULTIBOOT: u8 = 0;
+    pub const UHYVE: u8 = 1;
+    pub const LINUX_BOOT: u8 = 2;
+}
 
-    /// MULTIBOOT ONLY: Multiboot boot information address.
-    #[cfg(target_arch = "x86_64")]
-    pub mb_info: u64,
+impl TryFrom<&RawBootInfo> for BootInfo {
+    type Error = BootInfoError;
+
+    fn try_from(raw: &RawBootInfo) -> Result<Self, Self::Error> {
+        if raw.magic_number != RAW_BOOT_INFO_MAGIC_NUMBER {
+            return Err(BootInfoError::InvalidMagicNumber);
+        }
+
+        #[cfg(any(target_arch = "aarch64", target_arch = "riscv64"))]
+        let phys_addr_start = raw.ram_start;
+        #[cfg(not(any(target_arch = "aarch64", target_arch = "riscv64")))]
+        let phys_addr_start = 0;
+
+        let hardware_info = HardwareInfo {
+            phys_addr_range: phys_addr_start..raw.limit,
+            serial_port_base: (raw.uartport != 0).then_some(raw.uartport),
+            device_tree: raw.device_tree(),
+            #[cfg(target_arch = "riscv64")]
+            boot_hart_id: raw.boot_hart_id,
+        };
+
+        let platform_info = match raw.uhyve {
+            platform_discriminant::MULTIBOOT => PlatformInfo::Multiboot {
+                command_line: (raw.cmdline != 0).then_some((raw.cmdline, raw.cmdsize)),
+                #[cfg(target_arch = "x86_64")]
+                multiboot_info_addr: raw.mb_info,
+            },
+            platform_discriminant::UHYVE => PlatformInfo::Uhyve {
+                num_cpus: raw.possible_cpus,
+                cpu_freq: NonZeroU32::new(raw.cpu_freq),
+                boot_time: raw.boot_gtod,
+            },
+            platform_discriminant::LINUX_BOOT => PlatformInfo::LinuxBoot {
+                command_line: (raw.cmdline != 0).then_some((raw.cmdline, raw.cmdsize)),
+                rsdp: raw.rsdp(),
+                boot_loader_name: (raw.boot_loader_name != 0)
+                    .then_some((raw.boot_loader_name, raw.boot_loader_name_size)),
+            },
+            discriminant => return Err(BootInfoError::UnknownPlatform(discriminant)),
+        };
+
+        Ok(Self {
+            base: raw.base,
+            image_size: raw.image_size,
+            load_offset: raw.load_offset(),
+            tls_info: TlsInfo {
+                start: raw.tls_start,
+                filesz: raw.tls_filesz,
+                memsz: raw.tls_memsz,
+                align: raw.tls_align,
+            },
+            hardware_info,
+            platform_info,
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -108,14 +237,14 @@ pub struct RawBootInfo {
     version: u32,
 
     base: u64,
-    #[cfg(target_arch = "aarch64")]
+    #[cfg(any(target_arch = "aarch64", target_arch = "riscv64"))]
     ram_start: u64,
     limit: u64,
     image_size: u64,
     tls_start: u64,
     tls_filesz: u64,
     tls_memsz: u64,
-    #[cfg(target_arch = "aarch64")]
+    #[cfg(any(target_arch = "aarch64", target_arch = "riscv64"))]
     tls_align: u64,
 
     /// The current stack address.
@@ -159,6 +288,10 @@ pub struct RawBootInfo {
     /// libhermit-rs deduces this from `cpu_online`.
     current_boot_id: u32,
 
+    /// Hart ID of the boot processor, as passed in `a0` at entry.
+    #[cfg(target_arch = "riscv64")]
+    boot_hart_id: u64,
+
     uartport: SerialPortBase,
 
     /// Single Kernel (legacy)
@@ -184,6 +317,29 @@ pub struct RawBootInfo {
     /// Was used by lwIP once.
     hcmask: [u8; 4],
 
+    /// Physical address of a flattened device tree (FDT).
+    ///
+    /// Zero if the boot loader did not provide one, e.g. on uhyve and multiboot.
+    device_tree: u64,
+
+    /// Difference between `base` and the kernel's link-time base address.
+    ///
+    /// Zero if the image was loaded at its link-time address.
+    load_offset: u64,
+
+    /// Physical address of the ACPI RSDP, as obtained from UEFI firmware.
+    ///
+    /// Zero if no firmware-provided RSDP is available, e.g. on uhyve and multiboot.
+    rsdp: u64,
+
+    /// Boot loader name pointer, as obtained from UEFI firmware.
+    ///
+    /// Zero if no firmware-provided boot loader name is available, e.g. on uhyve and multiboot.
+    boot_loader_name: u64,
+
+    /// Boot loader name length.
+    boot_loader_name_size: u64,
+
     #[cfg(target_arch = "x86_64")]
     tls_align: u64,
 }
@@ -197,4 +353,25 @@ impl RawBootInfo {
     pub fn load_cpu_online(&self) -> u32 {
         self.cpu_online.load(Ordering::Acquire)
     }
+
+    /// Physical address of a flattened device tree (FDT), if the boot loader provided one.
+    pub fn device_tree(&self) -> Option<NonZeroU64> {
+        NonZeroU64::new(self.device_tree)
+    }
+
+    /// Difference between the load-time base address (`base`) and the
+    /// kernel's link-time base address.
+    ///
+    /// The kernel adds this to the addend of every `R_*_RELATIVE` relocation
+    /// in its `.rela.dyn` to relocate itself before enabling paging. Zero if
+    /// the image is not relocatable, i.e. it was loaded at its link-time
+    /// address.
+    pub fn load_offset(&self) -> u64 {
+        self.load_offset
+    }
+
+    /// Physical address of the ACPI RSDP, if provided by firmware.
+    pub fn rsdp(&self) -> Option<NonZeroU64> {
+        NonZeroU64::new(self.rsdp)
+    }
 }